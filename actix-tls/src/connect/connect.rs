@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+
+/// Combination of hostname and port used as a connector request's destination.
+pub trait Address: Unpin + 'static {
+    /// Hostname to be used for connection and TLS certificate checking.
+    fn hostname(&self) -> &str;
+
+    /// Optional port to be used for connection, if unspecified by the caller.
+    fn port(&self) -> Option<u16> {
+        None
+    }
+}
+
+impl Address for String {
+    fn hostname(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Address for &'static str {
+    fn hostname(&self) -> &str {
+        self
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub(crate) enum ConnectAddrs {
+    None,
+    One(SocketAddr),
+    Multi(VecDeque<SocketAddr>),
+}
+
+impl Default for ConnectAddrs {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl ConnectAddrs {
+    pub(crate) fn is_none(&self) -> bool {
+        matches!(self, ConnectAddrs::None)
+    }
+}
+
+/// A request to open a TCP (or other transport) connection, carried through the connector
+/// service stack.
+#[derive(Eq, PartialEq, Debug, Hash)]
+pub struct Connect<T> {
+    pub(crate) req: T,
+    pub(crate) port: u16,
+    pub(crate) addr: ConnectAddrs,
+    pub(crate) local_addr: Option<SocketAddr>,
+}
+
+impl<T: Address> Connect<T> {
+    /// Create a new `Connect` request for the given address/host.
+    pub fn new(req: T) -> Connect<T> {
+        let port = req.port().unwrap_or(0);
+        Connect {
+            req,
+            port,
+            addr: ConnectAddrs::None,
+            local_addr: None,
+        }
+    }
+
+    /// Provide pre-resolved addresses for the request.
+    pub fn set_addrs<I>(mut self, addrs: I) -> Self
+    where
+        I: IntoIterator<Item = SocketAddr>,
+    {
+        let mut addrs = addrs.into_iter().collect::<VecDeque<_>>();
+        self.addr = if addrs.len() < 2 {
+            match addrs.pop_front() {
+                Some(addr) => ConnectAddrs::One(addr),
+                None => ConnectAddrs::None,
+            }
+        } else {
+            ConnectAddrs::Multi(addrs)
+        };
+        self
+    }
+
+    /// Set the port of the request, overriding the one given by `Address::port`.
+    pub fn set_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Bind the outbound socket to `local_addr` before connecting, overriding the connector's
+    /// default for this request only.
+    ///
+    /// Useful for source-address selection on multi-homed hosts and for proxies that must pin
+    /// their egress IP.
+    pub fn set_local_addr(mut self, local_addr: SocketAddr) -> Self {
+        self.local_addr = Some(local_addr);
+        self
+    }
+
+    /// Returns the hostname of the request.
+    pub fn hostname(&self) -> &str {
+        self.req.hostname()
+    }
+
+    /// Returns the port of the request.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+/// Whether a resolved address (and the connection made to it) is IPv4 or IPv6.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AddressFamily {
+    /// The connection was made over IPv4.
+    V4,
+    /// The connection was made over IPv6.
+    V6,
+}
+
+/// Metadata describing how a [`Connection`]'s underlying transport was established.
+///
+/// Populated by connector services (e.g. [`TcpConnector`](super::TcpConnector)) from facts
+/// discovered while connecting, so that higher layers (TLS, ALPN negotiation, connection
+/// pools) don't need their own resolver to make decisions based on address selection.
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectInfo {
+    peer_addr: SocketAddr,
+    local_addr: Option<SocketAddr>,
+    attempts: usize,
+    family: AddressFamily,
+}
+
+impl ConnectInfo {
+    pub(crate) fn new(
+        peer_addr: SocketAddr,
+        local_addr: Option<SocketAddr>,
+        attempts: usize,
+    ) -> Self {
+        let family = if peer_addr.is_ipv6() {
+            AddressFamily::V6
+        } else {
+            AddressFamily::V4
+        };
+
+        ConnectInfo {
+            peer_addr,
+            local_addr,
+            attempts,
+            family,
+        }
+    }
+
+    /// Returns the resolved address that the connection was actually made to.
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer_addr
+    }
+
+    /// Returns the local address of the connecting socket, if it could be determined.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// Returns how many addresses were attempted (including the winning one) before this
+    /// connection succeeded.
+    ///
+    /// This is 0 for a connection that was reused from a connection pool rather than dialed.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    /// Returns whether the winning address was IPv4 or IPv6.
+    pub fn family(&self) -> AddressFamily {
+        self.family
+    }
+}
+
+/// An established connection, the request that produced it, and metadata about how it was
+/// connected.
+#[derive(Debug)]
+pub struct Connection<T, U> {
+    io: U,
+    req: T,
+    info: ConnectInfo,
+}
+
+impl<T, U> Connection<T, U> {
+    pub(crate) fn new(io: U, req: T, info: ConnectInfo) -> Self {
+        Connection { io, req, info }
+    }
+
+    /// Unwraps the connection into it's underlying IO object and request.
+    pub fn into_parts(self) -> (U, T) {
+        (self.io, self.req)
+    }
+
+    /// Unwraps the connection into it's underlying IO object, request, and connect metadata.
+    pub fn into_parts_with_info(self) -> (U, T, ConnectInfo) {
+        (self.io, self.req, self.info)
+    }
+
+    /// Returns a reference to the underlying IO object.
+    pub fn io(&self) -> &U {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying IO object.
+    pub fn io_mut(&mut self) -> &mut U {
+        &mut self.io
+    }
+
+    /// Returns metadata describing how this connection was established.
+    pub fn info(&self) -> &ConnectInfo {
+        &self.info
+    }
+}
@@ -5,25 +5,153 @@ use std::{
     net::SocketAddr,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use actix_rt::net::TcpStream;
+use actix_rt::time::{sleep, Sleep};
 use actix_service::{Service, ServiceFactory};
-use futures_core::{future::LocalBoxFuture, ready};
+use futures_core::future::LocalBoxFuture;
 use log::{error, trace};
 use tokio_util::sync::ReusableBoxFuture;
 
-use super::connect::{Address, Connect, ConnectAddrs, Connection};
+use super::connect::{Address, Connect, ConnectAddrs, ConnectInfo, Connection};
 use super::error::ConnectError;
 
-/// TCP connector service factory
+/// Default delay between starting successive connection attempts when racing multiple
+/// resolved addresses, per [RFC 8305] ("Happy Eyeballs").
+///
+/// [RFC 8305]: https://datatracker.ietf.org/doc/html/rfc8305
+const DEFAULT_CONNECT_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// Socket options applied to every outbound socket before it connects.
+#[derive(Debug, Copy, Clone, Default)]
+struct SocketOptions {
+    reuse_address: bool,
+    tcp_nodelay: bool,
+    keepalive: Option<Duration>,
+    send_buffer_size: Option<usize>,
+    recv_buffer_size: Option<usize>,
+}
+
+/// Shared, `Copy`-able configuration for [`TcpConnectorFactory`] and [`TcpConnector`].
 #[derive(Debug, Copy, Clone)]
-pub struct TcpConnectorFactory;
+struct ConnectorConfig {
+    happy_eyeballs: bool,
+    attempt_delay: Duration,
+    attempt_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    socket_opts: SocketOptions,
+}
+
+impl Default for ConnectorConfig {
+    fn default() -> Self {
+        ConnectorConfig {
+            happy_eyeballs: true,
+            attempt_delay: DEFAULT_CONNECT_ATTEMPT_DELAY,
+            attempt_timeout: None,
+            timeout: None,
+            socket_opts: SocketOptions::default(),
+        }
+    }
+}
+
+/// TCP connector service factory
+///
+/// # Breaking change
+///
+/// `TcpConnectorFactory` now carries the racing/timeout/socket-option configuration set up
+/// via its builder methods, and so is no longer a zero-sized marker type: code that
+/// constructed it as a bare unit-struct literal (`TcpConnectorFactory`) must switch to
+/// [`TcpConnectorFactory::new`] or `TcpConnectorFactory::default()`, both of which give the
+/// default configuration (racing enabled, no timeouts); use
+/// [`sequential`](TcpConnectorFactory::sequential) for the previous strictly sequential
+/// behavior.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TcpConnectorFactory {
+    config: ConnectorConfig,
+}
 
 impl TcpConnectorFactory {
+    /// Construct new TCP connector factory.
+    ///
+    /// Address racing (RFC 8305 "Happy Eyeballs") is enabled by default; see
+    /// [`sequential`](Self::sequential) to opt back into strictly sequential connection
+    /// attempts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the delay between starting successive connection attempts when racing multiple
+    /// resolved addresses.
+    ///
+    /// Defaults to 250ms. Has no effect when [`sequential`](Self::sequential) mode is used.
+    pub fn connection_attempt_delay(mut self, delay: Duration) -> Self {
+        self.config.attempt_delay = delay;
+        self
+    }
+
+    /// Disable address racing and fall back to trying resolved addresses strictly
+    /// sequentially, in the order the resolver returned them.
+    pub fn sequential(mut self) -> Self {
+        self.config.happy_eyeballs = false;
+        self
+    }
+
+    /// Set a timeout for each individual connection attempt.
+    ///
+    /// When an attempt doesn't complete within this time it is treated as a failed attempt:
+    /// in sequential mode the next address (if any) is dialed, and in racing mode the attempt
+    /// is simply dropped, leaving the other in-flight attempts running.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.attempt_timeout = Some(timeout);
+        self
+    }
+
+    /// Set an overall deadline spanning every attempt across all resolved addresses.
+    ///
+    /// If no connection succeeds before the deadline, the call fails with
+    /// [`ConnectError::Timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the default `SO_REUSEADDR` option on outbound sockets.
+    pub fn reuse_address(mut self, reuse_address: bool) -> Self {
+        self.config.socket_opts.reuse_address = reuse_address;
+        self
+    }
+
+    /// Set the default `TCP_NODELAY` option on outbound sockets.
+    pub fn tcp_nodelay(mut self, tcp_nodelay: bool) -> Self {
+        self.config.socket_opts.tcp_nodelay = tcp_nodelay;
+        self
+    }
+
+    /// Enable TCP keepalive on outbound sockets, probing after `duration` of inactivity.
+    pub fn keepalive(mut self, duration: Duration) -> Self {
+        self.config.socket_opts.keepalive = Some(duration);
+        self
+    }
+
+    /// Set the outbound socket's send buffer size (`SO_SNDBUF`).
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.config.socket_opts.send_buffer_size = Some(size);
+        self
+    }
+
+    /// Set the outbound socket's receive buffer size (`SO_RCVBUF`).
+    pub fn recv_buffer_size(mut self, size: usize) -> Self {
+        self.config.socket_opts.recv_buffer_size = Some(size);
+        self
+    }
+
     /// Create TCP connector service
     pub fn service(&self) -> TcpConnector {
-        TcpConnector
+        TcpConnector {
+            config: self.config,
+        }
     }
 }
 
@@ -42,8 +170,19 @@ impl<T: Address> ServiceFactory<Connect<T>> for TcpConnectorFactory {
 }
 
 /// TCP connector service
-#[derive(Debug, Copy, Clone)]
-pub struct TcpConnector;
+///
+/// # Breaking change
+///
+/// `TcpConnector` now carries the configuration produced by
+/// [`TcpConnectorFactory`](TcpConnectorFactory)'s builder methods and is no longer a
+/// zero-sized marker type: code that constructed it as a bare unit-struct literal
+/// (`TcpConnector`) must switch to `TcpConnector::default()`, which gives the default
+/// configuration (racing enabled, no timeouts) rather than the previous strictly sequential
+/// behavior.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct TcpConnector {
+    config: ConnectorConfig,
+}
 
 impl<T: Address> Service<Connect<T>> for TcpConnector {
     type Response = Connection<T, TcpStream>;
@@ -54,9 +193,104 @@ impl<T: Address> Service<Connect<T>> for TcpConnector {
 
     fn call(&self, req: Connect<T>) -> Self::Future {
         let port = req.port();
-        let Connect { req, addr, .. } = req;
+        let Connect {
+            req,
+            addr,
+            local_addr,
+            ..
+        } = req;
+
+        TcpConnectorResponse::new(req, port, addr, local_addr, self.config)
+    }
+}
+
+/// Builds a socket via `socket2`, applying the requested local bind address and socket
+/// options, then connects it to `addr` and hands back an `actix_rt` [`TcpStream`].
+async fn connect_with_options(
+    addr: SocketAddr,
+    local_addr: Option<SocketAddr>,
+    opts: SocketOptions,
+) -> io::Result<TcpStream> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.set_nonblocking(true)?;
+
+    if opts.reuse_address {
+        socket.set_reuse_address(true)?;
+    }
+    if opts.tcp_nodelay {
+        socket.set_nodelay(true)?;
+    }
+    if let Some(size) = opts.send_buffer_size {
+        socket.set_send_buffer_size(size)?;
+    }
+    if let Some(size) = opts.recv_buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(idle) = opts.keepalive {
+        socket.set_tcp_keepalive(&socket2::TcpKeepalive::new().with_time(idle))?;
+    }
+
+    if let Some(local_addr) = local_addr {
+        socket.bind(&local_addr.into())?;
+    }
+
+    // the socket is non-blocking, so `connect` is expected to return `WouldBlock` (Windows) or
+    // `InProgress` (Unix `EINPROGRESS`) while the handshake is in progress; completion is
+    // awaited below via socket readiness
+    match socket.connect(&addr.into()) {
+        Ok(()) => {}
+        Err(err)
+            if err.kind() == io::ErrorKind::WouldBlock
+                || err.kind() == io::ErrorKind::InProgress => {}
+        Err(err) => return Err(err),
+    }
+
+    let stream = TcpStream::from_std(socket.into())?;
+    stream.writable().await?;
 
-        TcpConnectorResponse::new(req, port, addr)
+    match stream.take_error()? {
+        Some(err) => Err(err),
+        None => Ok(stream),
+    }
+}
+
+/// A single, in-flight connection attempt against one resolved address, with an optional
+/// per-attempt timeout.
+struct Attempt {
+    addr: SocketAddr,
+    fut: ReusableBoxFuture<Result<TcpStream, io::Error>>,
+    timeout: Option<Pin<Box<Sleep>>>,
+}
+
+impl Attempt {
+    fn new(
+        addr: SocketAddr,
+        local_addr: Option<SocketAddr>,
+        socket_opts: SocketOptions,
+        timeout: Option<Duration>,
+    ) -> Self {
+        Attempt {
+            addr,
+            fut: ReusableBoxFuture::new(connect_with_options(addr, local_addr, socket_opts)),
+            timeout: timeout.map(|dur| Box::pin(sleep(dur))),
+        }
+    }
+
+    /// Poll the underlying connect future, failing the attempt with a timed-out io error if
+    /// its timeout (if any) elapses first.
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<Result<TcpStream, io::Error>> {
+        if let Some(timeout) = self.timeout.as_mut() {
+            if timeout.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "connection attempt timed out",
+                )));
+            }
+        }
+
+        Pin::new(&mut self.fut).poll(cx)
     }
 }
 
@@ -66,13 +300,25 @@ pub enum TcpConnectorResponse<T> {
         req: Option<T>,
         port: u16,
         addrs: Option<VecDeque<SocketAddr>>,
-        stream: Option<ReusableBoxFuture<Result<TcpStream, io::Error>>>,
+        local_addr: Option<SocketAddr>,
+        config: ConnectorConfig,
+        delay: Option<Pin<Box<Sleep>>>,
+        deadline: Option<Pin<Box<Sleep>>>,
+        in_flight: Vec<Attempt>,
+        attempts: usize,
+        last_err: Option<io::Error>,
     },
     Error(Option<ConnectError>),
 }
 
 impl<T: Address> TcpConnectorResponse<T> {
-    pub(crate) fn new(req: T, port: u16, addr: ConnectAddrs) -> TcpConnectorResponse<T> {
+    pub(crate) fn new(
+        req: T,
+        port: u16,
+        addr: ConnectAddrs,
+        local_addr: Option<SocketAddr>,
+        config: ConnectorConfig,
+    ) -> TcpConnectorResponse<T> {
         if addr.is_none() {
             error!("TCP connector: unresolved connection address");
             return TcpConnectorResponse::Error(Some(ConnectError::Unresolved));
@@ -84,6 +330,8 @@ impl<T: Address> TcpConnectorResponse<T> {
             port
         );
 
+        let deadline = config.timeout.map(|dur| Box::pin(sleep(dur)));
+
         match addr {
             ConnectAddrs::None => unreachable!("none variant already checked"),
 
@@ -91,17 +339,51 @@ impl<T: Address> TcpConnectorResponse<T> {
                 req: Some(req),
                 port,
                 addrs: None,
-                stream: Some(ReusableBoxFuture::new(TcpStream::connect(addr))),
+                local_addr,
+                config,
+                delay: None,
+                deadline,
+                in_flight: vec![Attempt::new(
+                    addr,
+                    local_addr,
+                    config.socket_opts,
+                    config.attempt_timeout,
+                )],
+                attempts: 1,
+                last_err: None,
             },
 
-            // when resolver returns multiple socket addr for request they would be popped from
-            // front end of queue and returns with the first successful tcp connection.
-            ConnectAddrs::Multi(addrs) => TcpConnectorResponse::Response {
-                req: Some(req),
-                port,
-                addrs: Some(addrs),
-                stream: None,
-            },
+            // When racing is enabled, addresses are first interleaved by family (IPv6, IPv4,
+            // IPv6, ...) per RFC 8305 so that, combined with the attempt delay below, both
+            // families get an early attempt instead of stalling behind a single unreachable
+            // route.
+            ConnectAddrs::Multi(addrs) => {
+                let mut addrs = if config.happy_eyeballs {
+                    interleave_addrs(addrs)
+                } else {
+                    addrs
+                };
+
+                let first = addrs.pop_front().unwrap();
+
+                TcpConnectorResponse::Response {
+                    req: Some(req),
+                    port,
+                    addrs: Some(addrs),
+                    local_addr,
+                    config,
+                    delay: None,
+                    deadline,
+                    in_flight: vec![Attempt::new(
+                        first,
+                        local_addr,
+                        config.socket_opts,
+                        config.attempt_timeout,
+                    )],
+                    attempts: 1,
+                    last_err: None,
+                }
+            }
         }
     }
 }
@@ -117,42 +399,319 @@ impl<T: Address> Future for TcpConnectorResponse<T> {
                 req,
                 port,
                 addrs,
-                stream,
+                local_addr,
+                config,
+                delay,
+                deadline,
+                in_flight,
+                attempts,
+                last_err,
             } => loop {
-                if let Some(new) = stream.as_mut() {
-                    match ready!(new.poll(cx)) {
-                        Ok(sock) => {
+                // poll every in-flight connection attempt before the overall deadline below,
+                // so a connection that succeeds on the same wake-up as the deadline firing is
+                // still returned instead of being discarded for a spurious timeout; the first
+                // to succeed wins the race and the rest are dropped (cancelling them)
+                let mut i = 0;
+                while i < in_flight.len() {
+                    match in_flight[i].poll(cx) {
+                        Poll::Ready(Ok(sock)) => {
                             let req = req.take().unwrap();
                             trace!(
                                 "TCP connector: successfully connected to {:?} - {:?}",
                                 req.hostname(),
                                 sock.peer_addr()
                             );
-                            return Poll::Ready(Ok(Connection::new(sock, req)));
+                            let info = ConnectInfo::new(
+                                in_flight[i].addr,
+                                sock.local_addr().ok(),
+                                *attempts,
+                            );
+                            return Poll::Ready(Ok(Connection::new(sock, req, info)));
                         }
 
-                        Err(err) => {
+                        Poll::Ready(Err(err)) => {
                             trace!(
                                 "TCP connector: failed to connect to {:?} port: {}",
                                 req.as_ref().unwrap().hostname(),
                                 port,
                             );
-
-                            if addrs.is_none() || addrs.as_ref().unwrap().is_empty() {
-                                return Poll::Ready(Err(ConnectError::Io(err)));
-                            }
+                            *last_err = Some(err);
+                            in_flight.remove(i);
                         }
+
+                        Poll::Pending => i += 1,
                     }
                 }
 
-                // try to connect
-                let addr = addrs.as_mut().unwrap().pop_front().unwrap();
+                if let Some(deadline) = deadline.as_mut() {
+                    if deadline.as_mut().poll(cx).is_ready() {
+                        return Poll::Ready(Err(ConnectError::Timeout));
+                    }
+                }
+
+                let addrs_exhausted = addrs.as_ref().map_or(true, VecDeque::is_empty);
 
-                match stream {
-                    Some(rbf) => rbf.set(TcpStream::connect(addr)),
-                    None => *stream = Some(ReusableBoxFuture::new(TcpStream::connect(addr))),
+                if in_flight.is_empty() && addrs_exhausted {
+                    return Poll::Ready(Err(ConnectError::Io(last_err.take().unwrap_or_else(
+                        || io::Error::new(io::ErrorKind::NotConnected, "no addresses to connect"),
+                    ))));
                 }
+
+                if addrs_exhausted {
+                    return Poll::Pending;
+                }
+
+                // in sequential mode the next address is only dialed once the previous
+                // attempt(s) have failed; in racing mode it is dialed as soon as the attempt
+                // delay timer fires, regardless of whether earlier attempts are still pending
+                let should_start_next = if in_flight.is_empty() {
+                    // the only (or last remaining) attempt failed before its delay timer fired;
+                    // drop that stale timer so the next attempt started below re-arms its own
+                    // delay from when it actually starts, instead of reusing a timer armed from
+                    // the failed attempt's start time (which could fire early or instantly)
+                    *delay = None;
+                    true
+                } else if !config.happy_eyeballs {
+                    false
+                } else {
+                    let timer = delay.get_or_insert_with(|| Box::pin(sleep(config.attempt_delay)));
+                    match timer.as_mut().poll(cx) {
+                        Poll::Ready(()) => {
+                            *delay = None;
+                            true
+                        }
+                        Poll::Pending => false,
+                    }
+                };
+
+                if !should_start_next {
+                    return Poll::Pending;
+                }
+
+                let addr = addrs.as_mut().unwrap().pop_front().unwrap();
+                trace!("TCP connector: connecting to {:?}", addr);
+                in_flight.push(Attempt::new(
+                    addr,
+                    *local_addr,
+                    config.socket_opts,
+                    config.attempt_timeout,
+                ));
+                *attempts += 1;
             },
         }
     }
 }
+
+/// Reorders resolved addresses so that IPv6 and IPv4 addresses alternate, starting with
+/// IPv6, as recommended by RFC 8305 for Happy Eyeballs racing.
+fn interleave_addrs(addrs: VecDeque<SocketAddr>) -> VecDeque<SocketAddr> {
+    let (mut v6, mut v4): (VecDeque<_>, VecDeque<_>) =
+        addrs.into_iter().partition(|addr| addr.is_ipv6());
+
+    let mut interleaved = VecDeque::with_capacity(v6.len() + v4.len());
+    loop {
+        match (v6.pop_front(), v4.pop_front()) {
+            (Some(a), Some(b)) => {
+                interleaved.push_back(a);
+                interleaved.push_back(b);
+            }
+            (Some(a), None) => {
+                interleaved.push_back(a);
+                interleaved.extend(v6);
+                break;
+            }
+            (None, Some(b)) => {
+                interleaved.push_back(b);
+                interleaved.extend(v4);
+                break;
+            }
+            (None, None) => break,
+        }
+    }
+
+    interleaved
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_rt::net::TcpListener;
+
+    use super::*;
+
+    fn v4(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    fn v6(port: u16) -> SocketAddr {
+        SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], port))
+    }
+
+    /// Binds an ephemeral loopback port and immediately drops the listener, leaving behind an
+    /// address that nothing is listening on. Connecting to it fails fast (connection refused)
+    /// instead of hanging, giving tests a deterministic "unreachable" address.
+    fn closed_addr() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    #[test]
+    fn interleave_addrs_alternates_starting_with_v6() {
+        let addrs = VecDeque::from([v4(1), v4(2), v6(3), v6(4)]);
+        let interleaved = interleave_addrs(addrs);
+        assert_eq!(
+            Vec::from(interleaved),
+            vec![v6(3), v4(1), v6(4), v4(2)],
+            "should alternate families, starting with IPv6, preserving each family's order"
+        );
+    }
+
+    #[test]
+    fn interleave_addrs_appends_the_leftover_tail() {
+        // more v4 addresses than v6: once v6 is exhausted the rest of v4 is appended in order
+        let addrs = VecDeque::from([v6(1), v4(2), v4(3), v4(4)]);
+        assert_eq!(
+            Vec::from(interleave_addrs(addrs)),
+            vec![v6(1), v4(2), v4(3), v4(4)]
+        );
+    }
+
+    #[test]
+    fn interleave_addrs_handles_single_family() {
+        let addrs = VecDeque::from([v4(1), v4(2)]);
+        assert_eq!(Vec::from(interleave_addrs(addrs)), vec![v4(1), v4(2)]);
+    }
+
+    #[actix_rt::test]
+    async fn attempt_poll_fails_with_timed_out_once_its_timer_elapses() {
+        let mut attempt = Attempt {
+            addr: v4(1),
+            fut: ReusableBoxFuture::new(std::future::pending()),
+            timeout: Some(Box::pin(sleep(Duration::from_millis(1)))),
+        };
+
+        let err = std::future::poll_fn(|cx| attempt.poll(cx)).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[actix_rt::test]
+    async fn sequential_mode_moves_on_after_an_unreachable_address_fails() {
+        let bad = closed_addr();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let good = listener.local_addr().unwrap();
+
+        let config = ConnectorConfig {
+            happy_eyeballs: false,
+            ..ConnectorConfig::default()
+        };
+
+        let mut response = TcpConnectorResponse::new(
+            "example.com".to_string(),
+            good.port(),
+            ConnectAddrs::Multi(VecDeque::from([bad, good])),
+            None,
+            config,
+        );
+
+        let (result, _accepted) = tokio::join!(
+            std::future::poll_fn(|cx| Pin::new(&mut response).poll(cx)),
+            async { listener.accept().await.unwrap() },
+        );
+
+        let (io, _req, info) = result.unwrap().into_parts_with_info();
+        drop(io);
+        assert_eq!(info.peer_addr(), good);
+    }
+
+    #[actix_rt::test]
+    async fn racing_mode_starts_the_next_attempt_without_waiting_for_the_first_to_fail() {
+        let config = ConnectorConfig {
+            happy_eyeballs: true,
+            attempt_delay: Duration::from_millis(1),
+            ..ConnectorConfig::default()
+        };
+
+        let mut response = TcpConnectorResponse::Response {
+            req: Some("example.com".to_string()),
+            port: 0,
+            addrs: Some(VecDeque::from([v4(2)])),
+            local_addr: None,
+            config,
+            delay: None,
+            deadline: None,
+            in_flight: vec![Attempt {
+                addr: v4(1),
+                fut: ReusableBoxFuture::new(std::future::pending()),
+                timeout: None,
+            }],
+            attempts: 1,
+            last_err: None,
+        };
+
+        let mut armed = false;
+        std::future::poll_fn(|cx| {
+            // first poll: the only in-flight attempt is pending, which arms the attempt-delay
+            // timer; once that timer elapses it wakes this future again for a second poll,
+            // without anything ever resolving the still-pending first attempt
+            let _ = Pin::new(&mut response).poll(cx);
+            if !armed {
+                armed = true;
+                return Poll::Pending;
+            }
+            Poll::Ready(())
+        })
+        .await;
+
+        match &response {
+            TcpConnectorResponse::Response {
+                in_flight, addrs, ..
+            } => {
+                assert_eq!(
+                    in_flight.len(),
+                    2,
+                    "racing mode should start a second attempt once the delay elapses, \
+                     even though the first attempt is still pending"
+                );
+                assert!(addrs.as_ref().unwrap().is_empty());
+            }
+            TcpConnectorResponse::Error(_) => panic!("expected the Response variant"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn poll_prefers_an_in_flight_success_over_a_same_tick_deadline() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (client, _server) =
+            tokio::join!(TcpStream::connect(addr), async { listener.accept().await.unwrap() });
+
+        let mut response = TcpConnectorResponse::Response {
+            req: Some("example.com".to_string()),
+            port: addr.port(),
+            addrs: Some(VecDeque::new()),
+            local_addr: None,
+            config: ConnectorConfig::default(),
+            delay: None,
+            // already-elapsed deadline: resolves on the very first poll, same as the
+            // already-ready in-flight attempt below
+            deadline: Some(Box::pin(sleep(Duration::ZERO))),
+            in_flight: vec![Attempt {
+                addr,
+                fut: ReusableBoxFuture::new(std::future::ready(Ok(client.unwrap()))),
+                timeout: None,
+            }],
+            attempts: 1,
+            last_err: None,
+        };
+
+        // let the zero-duration deadline actually register as elapsed before polling
+        sleep(Duration::from_millis(1)).await;
+
+        let result = std::future::poll_fn(|cx| Pin::new(&mut response).poll(cx)).await;
+        assert!(
+            result.is_ok(),
+            "a connection that succeeds on the same wake-up as the deadline must win the race, \
+             not be discarded for a spurious timeout"
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std::{fmt, io};
+
+/// Connector error
+#[derive(Debug)]
+pub enum ConnectError {
+    /// Failed to resolve the hostname
+    Resolver(Box<dyn std::error::Error>),
+
+    /// No dns records
+    NoRecords,
+
+    /// Connector received an unresolved address
+    Unresolved,
+
+    /// Connecting took too long
+    Timeout,
+
+    /// Connection io error
+    Io(io::Error),
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::Resolver(e) => write!(f, "failed resolving hostname: {}", e),
+            ConnectError::NoRecords => write!(f, "no dns records found for the input"),
+            ConnectError::Unresolved => write!(f, "connector received unresolved address"),
+            ConnectError::Timeout => write!(f, "timed out while establishing connection"),
+            ConnectError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ConnectError {}
+
+impl From<io::Error> for ConnectError {
+    fn from(err: io::Error) -> ConnectError {
+        ConnectError::Io(err)
+    }
+}
@@ -0,0 +1,544 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    fmt, io,
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use actix_rt::net::TcpStream;
+use actix_service::{Service, ServiceFactory};
+use futures_core::future::LocalBoxFuture;
+use log::trace;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use super::connect::{Address, Connect, ConnectInfo, Connection};
+use super::connector::{TcpConnector, TcpConnectorFactory};
+use super::error::ConnectError;
+
+/// Default max idle time a pooled connection may sit unused before it is evicted.
+const DEFAULT_CONN_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Default max total age of a pooled connection, regardless of use, before it is evicted.
+const DEFAULT_CONN_LIFETIME: Duration = Duration::from_secs(75);
+
+/// Default cap on live (checked out) connections tracked per destination.
+const DEFAULT_MAX_PER_HOST: usize = 100;
+
+/// Identifies the destination a pooled connection belongs to.
+type PoolKey = (Rc<str>, u16);
+
+struct IdleEntry {
+    io: TcpStream,
+    established: Instant,
+    idle_since: Instant,
+}
+
+#[derive(Default)]
+struct PoolInner {
+    idle: HashMap<PoolKey, VecDeque<IdleEntry>>,
+    // paired with the semaphore's own starting size, so `prune_stale` can tell an untouched
+    // semaphore (safe to drop) from one some caller is still holding permits against
+    limits: HashMap<PoolKey, (Arc<Semaphore>, usize)>,
+}
+
+/// Shared, reference-counted connection pool state.
+#[derive(Clone)]
+struct Pool(Rc<RefCell<PoolInner>>);
+
+impl fmt::Debug for Pool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool").finish_non_exhaustive()
+    }
+}
+
+impl Pool {
+    fn new() -> Self {
+        Pool(Rc::new(RefCell::new(PoolInner::default())))
+    }
+
+    /// Take a still-healthy idle connection for `key`, evicting any stale or dead ones found
+    /// along the way (for every destination, not just `key` — see
+    /// [`prune_stale`](Self::prune_stale)).
+    ///
+    /// Returns the connection's original `established` time (i.e. when it first connected,
+    /// not when it was last checked out) so that `conn_lifetime` keeps being enforced across
+    /// repeated reuse cycles.
+    fn acquire(
+        &self,
+        key: &PoolKey,
+        keep_alive: Duration,
+        lifetime: Duration,
+    ) -> Option<(TcpStream, Instant)> {
+        self.prune_stale(keep_alive, lifetime);
+
+        let mut inner = self.0.borrow_mut();
+        let idle = inner.idle.get_mut(key)?;
+        let entry = idle.pop_front()?;
+        let now_empty = idle.is_empty();
+
+        if now_empty {
+            inner.idle.remove(key);
+        }
+
+        Some((entry.io, entry.established))
+    }
+
+    /// Returns the semaphore bounding the number of connections to `key` that may be checked
+    /// out *concurrently* via [`PooledConnector::call`], creating one sized `max_per_host` the
+    /// first time this destination is seen.
+    ///
+    /// This only bounds attempts and live (checked-out) [`PooledStream`]s: the permit is
+    /// released the moment a stream is dropped, at the same time the underlying socket (if
+    /// reusable) goes into the idle pool. So `max_per_host` caps concurrent checkouts, not the
+    /// total number of sockets (idle + checked out) kept open for a destination.
+    fn limit(&self, key: &PoolKey, max_per_host: usize) -> Arc<Semaphore> {
+        Arc::clone(
+            &self
+                .0
+                .borrow_mut()
+                .limits
+                .entry(key.clone())
+                .or_insert_with(|| (Arc::new(Semaphore::new(max_per_host)), max_per_host))
+                .0,
+        )
+    }
+
+    fn store_idle(&self, key: PoolKey, io: TcpStream, established: Instant) {
+        self.0
+            .borrow_mut()
+            .idle
+            .entry(key)
+            .or_default()
+            .push_back(IdleEntry {
+                io,
+                established,
+                idle_since: Instant::now(),
+            });
+    }
+
+    /// Evicts stale or dead idle connections across *every* destination, and drops the
+    /// bookkeeping (idle queue, `limits` semaphore) for any destination left with nothing
+    /// outstanding.
+    ///
+    /// Without this, a process that contacts many distinct hosts over its lifetime would
+    /// accumulate one `HashMap` entry (and any idle sockets sitting in it) per destination
+    /// forever, since `acquire` on its own only ever evicts entries for the one `key` being
+    /// looked up. The pool has no timer or background task of its own, so this instead
+    /// piggybacks on every `acquire` call: as long as the pool sees *any* traffic, to *any*
+    /// destination, abandoned destinations get swept out within `keep_alive`/`lifetime` of
+    /// going idle.
+    fn prune_stale(&self, keep_alive: Duration, lifetime: Duration) {
+        let mut inner = self.0.borrow_mut();
+        let PoolInner { idle, limits } = &mut *inner;
+
+        idle.retain(|_, entries| {
+            entries.retain(|entry| {
+                entry.idle_since.elapsed() <= keep_alive
+                    && entry.established.elapsed() <= lifetime
+                    && is_healthy(&entry.io)
+            });
+            !entries.is_empty()
+        });
+
+        // a semaphore is safe to drop once nothing references it: no idle connections remain
+        // for this destination, no permit is currently held against it (`available_permits`
+        // back at the starting size), and no pending `call` future still holds a clone of it
+        limits.retain(|key, (sem, max)| {
+            idle.contains_key(key) || sem.available_permits() < *max || Arc::strong_count(sem) > 1
+        });
+    }
+}
+
+/// Non-blocking health probe for an idle connection.
+///
+/// A recycled stream may have been half-closed by the peer while sitting idle, so before
+/// handing it back out we try a zero-effect read: no bytes available yet (`WouldBlock`) means
+/// the connection is still open, while `Ok(0)` (EOF) or any other error means the peer has
+/// gone away and the connection must be discarded.
+fn is_healthy(io: &TcpStream) -> bool {
+    let mut buf = [0u8; 1];
+    match io.try_read(&mut buf) {
+        Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => true,
+        _ => false,
+    }
+}
+
+/// Service factory that wraps [`TcpConnectorFactory`] with a per-destination pool of idle,
+/// reusable connections.
+#[derive(Clone)]
+pub struct PooledConnectorFactory {
+    connector: TcpConnectorFactory,
+    conn_keep_alive: Duration,
+    conn_lifetime: Duration,
+    max_per_host: usize,
+    pool: Pool,
+}
+
+impl fmt::Debug for PooledConnectorFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledConnectorFactory")
+            .field("conn_keep_alive", &self.conn_keep_alive)
+            .field("conn_lifetime", &self.conn_lifetime)
+            .field("max_per_host", &self.max_per_host)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for PooledConnectorFactory {
+    fn default() -> Self {
+        PooledConnectorFactory {
+            connector: TcpConnectorFactory::default(),
+            conn_keep_alive: DEFAULT_CONN_KEEP_ALIVE,
+            conn_lifetime: DEFAULT_CONN_LIFETIME,
+            max_per_host: DEFAULT_MAX_PER_HOST,
+            pool: Pool::new(),
+        }
+    }
+}
+
+impl PooledConnectorFactory {
+    /// Wrap `connector` with a connection pool, using default pool settings.
+    pub fn new(connector: TcpConnectorFactory) -> Self {
+        PooledConnectorFactory {
+            connector,
+            ..Self::default()
+        }
+    }
+
+    /// Set the max idle time a pooled connection may sit unused before it is evicted.
+    ///
+    /// Defaults to 15 seconds.
+    pub fn conn_keep_alive(mut self, dur: Duration) -> Self {
+        self.conn_keep_alive = dur;
+        self
+    }
+
+    /// Set the max total age of a pooled connection, regardless of use, before it is evicted.
+    ///
+    /// Defaults to 75 seconds.
+    pub fn conn_lifetime(mut self, dur: Duration) -> Self {
+        self.conn_lifetime = dur;
+        self
+    }
+
+    /// Cap the number of connections to a single destination that may be checked out
+    /// concurrently.
+    ///
+    /// Once `max` connections to a destination are checked out, further calls for that same
+    /// destination wait until one is returned (or dropped) rather than opening additional
+    /// sockets, mirroring the `actix-web` connector's per-host connection limit.
+    pub fn max_per_host(mut self, max: usize) -> Self {
+        self.max_per_host = max;
+        self
+    }
+
+    /// Create the pooled connector service.
+    pub fn service(&self) -> PooledConnector {
+        PooledConnector {
+            connector: self.connector.service(),
+            conn_keep_alive: self.conn_keep_alive,
+            conn_lifetime: self.conn_lifetime,
+            max_per_host: self.max_per_host,
+            pool: self.pool.clone(),
+        }
+    }
+}
+
+impl<T: Address> ServiceFactory<Connect<T>> for PooledConnectorFactory {
+    type Response = Connection<T, PooledStream>;
+    type Error = ConnectError;
+    type Config = ();
+    type Service = PooledConnector;
+    type InitError = ();
+    type Future = LocalBoxFuture<'static, Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        let service = self.service();
+        Box::pin(async move { Ok(service) })
+    }
+}
+
+/// Service that wraps [`TcpConnector`] with a per-destination pool of idle, reusable
+/// connections.
+#[derive(Clone)]
+pub struct PooledConnector {
+    connector: TcpConnector,
+    conn_keep_alive: Duration,
+    conn_lifetime: Duration,
+    max_per_host: usize,
+    pool: Pool,
+}
+
+impl fmt::Debug for PooledConnector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledConnector").finish_non_exhaustive()
+    }
+}
+
+impl<T: Address> Service<Connect<T>> for PooledConnector {
+    type Response = Connection<T, PooledStream>;
+    type Error = ConnectError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, req: Connect<T>) -> Self::Future {
+        let key: PoolKey = (Rc::from(req.hostname()), req.port());
+
+        let pool = self.pool.clone();
+        let connector = self.connector.clone();
+        let conn_keep_alive = self.conn_keep_alive;
+        let conn_lifetime = self.conn_lifetime;
+        let limit = pool.limit(&key, self.max_per_host);
+
+        Box::pin(async move {
+            // bounds concurrently checked-out connections to this destination; waits here
+            // once `max_per_host` are outstanding. Held as a plain local for now: if this
+            // future is dropped before resolving (caller timeout, `select!`, ...) the permit
+            // is simply dropped along with it, releasing the slot with no extra bookkeeping.
+            // Ownership moves into the `PooledStream` below once a connection is ready.
+            let permit = limit
+                .acquire_owned()
+                .await
+                .expect("connection pool semaphore is never closed");
+
+            if let Some((io, established)) = pool.acquire(&key, conn_keep_alive, conn_lifetime) {
+                trace!("connection pool: reusing pooled connection to {:?}", key.0);
+
+                let info = ConnectInfo::new(io.peer_addr()?, io.local_addr().ok(), 0);
+                let stream = PooledStream::new(io, key, pool, established, permit);
+
+                let Connect { req: inner, .. } = req;
+                return Ok(Connection::new(stream, inner, info));
+            }
+
+            let established = Instant::now();
+
+            let conn = connector.call(req).await?;
+            let (io, inner, info) = conn.into_parts_with_info();
+            let stream = PooledStream::new(io, key, pool, established, permit);
+            Ok(Connection::new(stream, inner, info))
+        })
+    }
+}
+
+/// RAII wrapper around a pooled [`TcpStream`] that returns the connection to the pool on
+/// drop, but only when the consumer has called [`release`](Self::release) first to mark it
+/// as cleanly reusable.
+///
+/// This avoids the "partial request left on the wire poisons the connection" problem: a
+/// stream dropped mid-request (e.g. because the caller bailed out partway through writing
+/// it) is simply closed instead of being handed to the next caller in a corrupt state.
+pub struct PooledStream {
+    io: Option<TcpStream>,
+    key: PoolKey,
+    pool: Pool,
+    established: Instant,
+    reusable: bool,
+    // holds this destination's per-host connection slot for as long as the stream is
+    // checked out; dropped (releasing the slot) alongside the rest of `self`
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledStream {
+    fn new(
+        io: TcpStream,
+        key: PoolKey,
+        pool: Pool,
+        established: Instant,
+        permit: OwnedSemaphorePermit,
+    ) -> Self {
+        PooledStream {
+            io: Some(io),
+            key,
+            pool,
+            established,
+            reusable: false,
+            _permit: permit,
+        }
+    }
+
+    /// Mark this connection as having completed a clean request/response cycle, making it
+    /// eligible to be returned to the pool and reused once dropped.
+    pub fn release(&mut self) {
+        self.reusable = true;
+    }
+}
+
+impl fmt::Debug for PooledStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PooledStream")
+            .field("reusable", &self.reusable)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PooledStream {
+    fn drop(&mut self) {
+        if self.reusable {
+            if let Some(io) = self.io.take() {
+                self.pool.store_idle(self.key.clone(), io, self.established);
+            }
+        }
+    }
+}
+
+impl AsyncRead for PooledStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let io = self.get_mut().io.as_mut().expect("stream already closed");
+        Pin::new(io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for PooledStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let io = self.get_mut().io.as_mut().expect("stream already closed");
+        Pin::new(io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let io = self.get_mut().io.as_mut().expect("stream already closed");
+        Pin::new(io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let io = self.get_mut().io.as_mut().expect("stream already closed");
+        Pin::new(io).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_rt::net::TcpListener;
+
+    use super::*;
+
+    fn key(host: &str) -> PoolKey {
+        (Rc::from(host), 0)
+    }
+
+    /// Opens a real loopback TCP pair so tests exercise actual `TcpStream`s (the health probe
+    /// in `is_healthy` relies on `try_read`, which isn't meaningful on a mock).
+    ///
+    /// The accepted side is leaked into the returned tuple so it stays open for the duration of
+    /// the test; otherwise the client side would observe EOF and `is_healthy` would (correctly)
+    /// report it as dead.
+    async fn loopback_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (client, (server, _)) =
+            tokio::join!(TcpStream::connect(addr), async { listener.accept().await.unwrap() });
+
+        (client.unwrap(), server)
+    }
+
+    fn permit(max: usize) -> OwnedSemaphorePermit {
+        Arc::new(Semaphore::new(max)).try_acquire_owned().unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn acquire_returns_none_for_unknown_destination() {
+        let pool = Pool::new();
+        assert!(pool
+            .acquire(&key("example.com"), Duration::from_secs(15), Duration::from_secs(75))
+            .is_none());
+    }
+
+    #[actix_rt::test]
+    async fn acquire_preserves_original_established_time() {
+        let pool = Pool::new();
+        let k = key("example.com");
+        let (client, _server) = loopback_pair().await;
+
+        let established = Instant::now() - Duration::from_secs(5);
+        pool.store_idle(k.clone(), client, established);
+
+        let (_io, got_established) = pool
+            .acquire(&k, Duration::from_secs(15), Duration::from_secs(75))
+            .expect("connection should be returned");
+
+        // the original `established` instant must survive the round trip through the pool,
+        // not be reset to the time of the `acquire` call
+        assert_eq!(got_established, established);
+    }
+
+    #[actix_rt::test]
+    async fn acquire_evicts_connections_past_keep_alive() {
+        let pool = Pool::new();
+        let k = key("example.com");
+        let (client, _server) = loopback_pair().await;
+
+        // store as having gone idle before `keep_alive`, but well within `lifetime`
+        pool.0.borrow_mut().idle.entry(k.clone()).or_default().push_back(IdleEntry {
+            io: client,
+            established: Instant::now(),
+            idle_since: Instant::now() - Duration::from_secs(30),
+        });
+
+        let got = pool.acquire(&k, Duration::from_secs(15), Duration::from_secs(75));
+        assert!(got.is_none(), "stale idle connection should have been evicted");
+    }
+
+    #[actix_rt::test]
+    async fn acquire_evicts_connections_past_lifetime() {
+        let pool = Pool::new();
+        let k = key("example.com");
+        let (client, _server) = loopback_pair().await;
+
+        // freshly idle, but the connection itself is older than `lifetime`
+        pool.0.borrow_mut().idle.entry(k.clone()).or_default().push_back(IdleEntry {
+            io: client,
+            established: Instant::now() - Duration::from_secs(100),
+            idle_since: Instant::now(),
+        });
+
+        let got = pool.acquire(&k, Duration::from_secs(15), Duration::from_secs(75));
+        assert!(got.is_none(), "connection past conn_lifetime should have been evicted");
+    }
+
+    #[actix_rt::test]
+    async fn pooled_stream_dropped_without_release_is_not_returned_to_pool() {
+        let pool = Pool::new();
+        let k = key("example.com");
+        let (client, _server) = loopback_pair().await;
+
+        let stream = PooledStream::new(client, k.clone(), pool.clone(), Instant::now(), permit(1));
+        drop(stream);
+
+        assert!(pool
+            .acquire(&k, Duration::from_secs(15), Duration::from_secs(75))
+            .is_none());
+    }
+
+    #[actix_rt::test]
+    async fn pooled_stream_released_before_drop_is_returned_to_pool() {
+        let pool = Pool::new();
+        let k = key("example.com");
+        let (client, _server) = loopback_pair().await;
+
+        let mut stream =
+            PooledStream::new(client, k.clone(), pool.clone(), Instant::now(), permit(1));
+        stream.release();
+        drop(stream);
+
+        assert!(pool
+            .acquire(&k, Duration::from_secs(15), Duration::from_secs(75))
+            .is_some());
+    }
+}